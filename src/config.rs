@@ -0,0 +1,255 @@
+use std::path::PathBuf;
+use std::{env, fs, process::Command};
+
+use reqwest::{Client, Identity};
+use serde::Deserialize;
+
+use crate::Mailbox;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    MissingMailbox(String),
+    SecretResolution(String),
+    Reqwest(reqwest::Error),
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        ConfigError::Toml(error)
+    }
+}
+
+impl From<reqwest::Error> for ConfigError {
+    fn from(error: reqwest::Error) -> Self {
+        ConfigError::Reqwest(error)
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "i/o error: {}", e),
+            ConfigError::Toml(e) => write!(f, "invalid config TOML: {}", e),
+            ConfigError::MissingMailbox(name) => write!(f, "no mailbox named `{}` in config", name),
+            ConfigError::SecretResolution(reason) => write!(f, "failed to resolve secret: {}", reason),
+            ConfigError::Reqwest(e) => write!(f, "failed to build HTTP client: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Where a mailbox's password should be read from. Lets the same binary
+/// target integration, deployment, and production MESH environments
+/// without recompiling or hardcoding credentials.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretSource {
+    Env { env: String },
+    File { file: PathBuf },
+    Command { command: String },
+}
+
+impl SecretSource {
+    pub fn resolve(&self) -> Result<String, ConfigError> {
+        match self {
+            SecretSource::Env { env: name } => env::var(name)
+                .map_err(|_| ConfigError::SecretResolution(format!("env var {} not set", name))),
+            SecretSource::File { file } => Ok(fs::read_to_string(file)?.trim().to_string()),
+            SecretSource::Command { command } => {
+                let output = Command::new("sh").arg("-c").arg(command).output()?;
+                if !output.status.success() {
+                    return Err(ConfigError::SecretResolution(format!(
+                        "command `{}` exited with {}",
+                        command, output.status
+                    )));
+                }
+                Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+            }
+        }
+    }
+}
+
+/// A single named mailbox entry in the config file.
+#[derive(Debug, Deserialize)]
+pub struct MailboxConfig {
+    pub name: String,
+    pub url: String,
+    pub mailbox_id: String,
+    /// The MESH `SHARED_KEY` differs between environments, so it's read
+    /// per-mailbox rather than assumed to be `TestKey`.
+    pub shared_key: String,
+    pub password: SecretSource,
+    /// PEM-encoded client certificate presented for mutual TLS. Must be set
+    /// together with `client_key`; see [`MailboxConfig::build_client`].
+    pub client_cert: Option<PathBuf>,
+    /// PEM-encoded private key matching `client_cert`.
+    pub client_key: Option<PathBuf>,
+}
+
+impl MailboxConfig {
+    /// Resolves the configured secret and builds a [`Mailbox`] from this entry.
+    pub fn build(&self) -> Result<Mailbox, ConfigError> {
+        let password = self.password.resolve()?;
+        Ok(Mailbox::new(
+            self.url.clone(),
+            self.mailbox_id.clone(),
+            password,
+            self.shared_key.clone(),
+        ))
+    }
+
+    /// Builds the [`Client`] this mailbox should make requests with, presenting
+    /// `client_cert`/`client_key` as a TLS client identity when both are set.
+    pub fn build_client(&self) -> Result<Client, ConfigError> {
+        let mut builder = Client::builder().danger_accept_invalid_certs(true);
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert_path), Some(key_path)) => {
+                let cert_pem = fs::read(cert_path)?;
+                let key_pem = fs::read(key_path)?;
+                builder = builder.identity(Identity::from_pkcs8_pem(&cert_pem, &key_pem)?);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(ConfigError::SecretResolution(
+                    "client_cert and client_key must both be set, or neither".to_string(),
+                ))
+            }
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// The parsed contents of a `mesh.toml` config file defining one or more
+/// named mailboxes.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    #[serde(rename = "mailbox")]
+    pub mailboxes: Vec<MailboxConfig>,
+}
+
+impl Config {
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path.into())?;
+        Ok(toml::from_str(&text)?)
+    }
+
+    pub fn mailbox(&self, name: &str) -> Option<&MailboxConfig> {
+        self.mailboxes.iter().find(|m| m.name == name)
+    }
+
+    pub fn build_mailbox(&self, name: &str) -> Result<Mailbox, ConfigError> {
+        self.mailbox(name)
+            .ok_or_else(|| ConfigError::MissingMailbox(name.to_string()))?
+            .build()
+    }
+
+    pub fn build_client(&self, name: &str) -> Result<Client, ConfigError> {
+        self.mailbox(name)
+            .ok_or_else(|| ConfigError::MissingMailbox(name.to_string()))?
+            .build_client()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn mailbox_config(client_cert: Option<PathBuf>, client_key: Option<PathBuf>) -> MailboxConfig {
+        MailboxConfig {
+            name: "test".to_string(),
+            url: "https://example.invalid".to_string(),
+            mailbox_id: "X26ABC1".to_string(),
+            shared_key: "TestKey".to_string(),
+            password: SecretSource::Env {
+                env: "NHS_MESH_CLI_TEST_UNSET_VAR".to_string(),
+            },
+            client_cert,
+            client_key,
+        }
+    }
+
+    #[test]
+    fn secret_source_env_resolves_from_environment_variable() {
+        let var_name = format!("NHS_MESH_CLI_TEST_{}", Uuid::new_v4().simple());
+        env::set_var(&var_name, "hunter2");
+
+        let source = SecretSource::Env {
+            env: var_name.clone(),
+        };
+        assert_eq!(source.resolve().expect("resolve env secret"), "hunter2");
+
+        env::remove_var(&var_name);
+    }
+
+    #[test]
+    fn secret_source_env_errors_when_unset() {
+        let source = SecretSource::Env {
+            env: "NHS_MESH_CLI_TEST_DEFINITELY_UNSET".to_string(),
+        };
+        assert!(matches!(
+            source.resolve(),
+            Err(ConfigError::SecretResolution(_))
+        ));
+    }
+
+    #[test]
+    fn secret_source_file_resolves_and_trims_file_contents() {
+        let path = std::env::temp_dir().join(format!("nhs-mesh-cli-test-{}.secret", Uuid::new_v4()));
+        fs::write(&path, "  hunter2\n").expect("write secret file");
+
+        let source = SecretSource::File { file: path.clone() };
+        assert_eq!(source.resolve().expect("resolve file secret"), "hunter2");
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn secret_source_command_resolves_and_trims_stdout() {
+        let source = SecretSource::Command {
+            command: "echo hunter2".to_string(),
+        };
+        assert_eq!(source.resolve().expect("resolve command secret"), "hunter2");
+    }
+
+    #[test]
+    fn secret_source_command_errors_on_non_zero_exit() {
+        let source = SecretSource::Command {
+            command: "exit 1".to_string(),
+        };
+        assert!(matches!(
+            source.resolve(),
+            Err(ConfigError::SecretResolution(_))
+        ));
+    }
+
+    #[test]
+    fn build_client_rejects_a_one_sided_cert_key_pair() {
+        let cert_only = mailbox_config(Some(PathBuf::from("/nonexistent/cert.pem")), None);
+        assert!(matches!(
+            cert_only.build_client(),
+            Err(ConfigError::SecretResolution(_))
+        ));
+
+        let key_only = mailbox_config(None, Some(PathBuf::from("/nonexistent/key.pem")));
+        assert!(matches!(
+            key_only.build_client(),
+            Err(ConfigError::SecretResolution(_))
+        ));
+    }
+
+    #[test]
+    fn build_client_builds_without_a_client_identity_when_neither_is_set() {
+        let config = mailbox_config(None, None);
+        assert!(config.build_client().is_ok());
+    }
+}
@@ -1,37 +1,45 @@
 use chrono::Utc;
-use crossterm::{
-    self,
-    event::{self, Event, KeyCode},
-    terminal::{
-        self, disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-    },
-    ExecutableCommand,
-};
-use hmac::{digest::typenum::Length, Hmac, Mac};
 use log::{debug, error, info};
-use ratatui::{prelude::*, widgets::*};
 use reqwest::{
     header::{HeaderMap, HeaderValue},
-    Client, Error, Response,
+    Client, Response,
 };
 use serde_json::Value;
-use sha2::Sha256;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
-    io::{self, stdout},
+    time::Duration,
 };
-use uuid::{self, Uuid};
-
-const AUTH_SCHEMA_NAME: &str = "NHSMESH";
+use tokio::sync::{mpsc, Mutex};
+
+mod config;
+mod retry;
+mod store;
+mod token;
+mod tui;
+use config::Config;
+use retry::{with_retry, IsOnline, Retryable, RetryPolicy};
+use token::AuthTokenGenerator;
+
+/// Default `SHARED_KEY` used when no config file resolves one; MESH's
+/// actual shared key differs between environments (see [`config`]).
 const SHARED_KEY: &str = "TestKey";
 
+/// Messages larger than this are split into numbered chunks before upload,
+/// matching the MESH API's `Mex-Chunk-Range` protocol.
+const DEFAULT_CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
 #[derive(Debug)]
 pub enum MailboxError {
     ReqwestError(reqwest::Error),
     HeaderValueError(reqwest::header::InvalidHeaderValue),
     HeaderNameError(reqwest::header::InvalidHeaderName),
     ParseError(std::num::ParseIntError),
+    MissingField(&'static str),
+    HttpStatus {
+        status: reqwest::StatusCode,
+        retry_after: Option<Duration>,
+    },
 }
 
 impl From<reqwest::Error> for MailboxError {
@@ -40,6 +48,40 @@ impl From<reqwest::Error> for MailboxError {
     }
 }
 
+impl Retryable for MailboxError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            MailboxError::ReqwestError(e) => e.is_connect() || e.is_timeout(),
+            MailboxError::HttpStatus { status, .. } => {
+                status.is_server_error() || status.as_u16() == 429
+            }
+            _ => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            MailboxError::HttpStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// Builds a [`MailboxError::HttpStatus`] from a failed response, preserving
+/// the `Retry-After` header (if any) for [`with_retry`] to honour.
+fn mailbox_error_from_response(response: &Response) -> MailboxError {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .map(Duration::from_secs);
+    MailboxError::HttpStatus {
+        status: response.status(),
+        retry_after,
+    }
+}
+
 impl From<reqwest::header::InvalidHeaderValue> for MailboxError {
     fn from(error: reqwest::header::InvalidHeaderValue) -> Self {
         MailboxError::HeaderValueError(error)
@@ -52,179 +94,184 @@ impl From<reqwest::header::InvalidHeaderName> for MailboxError {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct Mailbox {
     url: String,
     id: String,
     password: String,
+    shared_key: String,
+    status: std::sync::Arc<Mutex<IsOnline>>,
+    token_generator: std::sync::Arc<Mutex<AuthTokenGenerator>>,
 }
 
 impl Mailbox {
-    pub fn new(url: String, id: String, password: String) -> Self {
-        Mailbox { url, id, password }
+    pub fn new(url: String, id: String, password: String, shared_key: String) -> Self {
+        Mailbox {
+            url,
+            id,
+            password,
+            shared_key,
+            status: std::sync::Arc::new(Mutex::new(IsOnline::Online)),
+            token_generator: std::sync::Arc::new(Mutex::new(AuthTokenGenerator::default())),
+        }
+    }
+
+    /// The mailbox's current connection status, as tracked by [`with_retry`].
+    pub async fn is_online(&self) -> IsOnline {
+        self.status.lock().await.clone()
+    }
+
+    async fn mark_online(&self) {
+        *self.status.lock().await = IsOnline::Online;
     }
-}
 
-// #[tokio::main]
-// async fn main() -> io::Result<()> {
-//     env::set_var("RUST_LOG", "info");
-//     env_logger::init();
-//     let sender_mailbox = Mailbox::new(
-//         "https://localhost:8700".to_string(),
-//         env::var("MESH_SENDER_MAILBOX_ID").unwrap_or("X26ABC1".to_string()),
-//         env::var("SENDER_MESH_PASSWORD").unwrap_or("password".to_string()),
-//     );
-//     let reciever_mailbox = Mailbox::new(
-//         "https://localhost:8700".to_string(),
-//         env::var("MESH_RECEIVER_MAILBOX_ID").unwrap_or("X26ABC2".to_string()),
-//         env::var("RECIEVER_MESH_PASSWORD").unwrap_or("password".to_string()),
-//     );
-//     let client = reqwest::Client::builder()
-//         .danger_accept_invalid_certs(true)
-//         .build()
-//         .expect("Failed to build client");
-
-//     enable_raw_mode()?;
-//     stdout().execute(EnterAlternateScreen)?;
-//     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-
-//     let mut should_quit = false;
-
-//     while !should_quit {
-//         terminal.draw(ui)?;
-//         should_quit = handle_events().await?;
-//     }
-
-//     disable_raw_mode()?;
-//     stdout().execute(LeaveAlternateScreen)?;
-//     Ok(())
-// }
-
-// async fn handle_events() -> io::Result<bool> {
-//     if event::poll(std::time::Duration::from_millis(50))? {
-//         if let Event::Key(key) = event::read()? {
-//             if key.kind == event::KeyEventKind::Press && key.code == KeyCode::Char('q') {
-//                 return Ok(true);
-//             }
-//         }
-//     }
-//     Ok(false)
-// }
-
-// fn ui(frame: &mut Frame) {
-//     let main_layout = Layout::new(
-//         Direction::Vertical,
-//         [
-//             Constraint::Length(1),
-//             Constraint::Min(0),
-//             Constraint::Length(1),
-//         ],
-//     )
-//     .split(frame.size());
-//     frame.render_widget(
-//         Block::new().borders(Borders::TOP).title("Title Bar"),
-//         main_layout[0],
-//     );
-//     frame.render_widget(
-//         Block::new().borders(Borders::TOP).title("Status Bar"),
-//         main_layout[2],
-//     );
-
-//     let inner_layout = Layout::new(
-//         Direction::Horizontal,
-//         [Constraint::Percentage(50), Constraint::Percentage(50)],
-//     )
-//     .split(main_layout[1]);
-//     frame.render_widget(
-//         Block::default().borders(Borders::ALL).title("Left"),
-//         inner_layout[0],
-//     );
-//     frame.render_widget(
-//         Block::default().borders(Borders::ALL).title("Right"),
-//         inner_layout[1],
-//     );
-// }
-
-// fn ui(frame: &mut Frame) {
-//     frame.render_widget(
-//         Paragraph::new("Welcome to the NHS MESH Mailbox Interface. Please make a selection from the options below")
-//             .block(Block::default().title("Greetings").borders(Borders::ALL)),
-//         frame.size(),
-//     )
-// }
+    async fn mark_offline(&self, last_error: String) {
+        let mut status = self.status.lock().await;
+        if let IsOnline::Offline { since, .. } = *status {
+            *status = IsOnline::Offline { since, last_error };
+        } else {
+            *status = IsOnline::Offline {
+                since: Utc::now(),
+                last_error,
+            };
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() {
     env::set_var("RUST_LOG", "debug");
     env_logger::init();
-    let sender_mailbox = Mailbox::new(
-        "https://localhost:8700".to_string(),
-        env::var("MESH_SENDER_MAILBOX_ID").unwrap_or("X26ABC1".to_string()),
-        env::var("SENDER_MESH_PASSWORD").unwrap_or("password".to_string()),
-    );
-    let reciever_mailbox = Mailbox::new(
-        "https://localhost:8700".to_string(),
-        env::var("MESH_RECEIVER_MAILBOX_ID").unwrap_or("X26ABC2".to_string()),
-        env::var("RECIEVER_MESH_PASSWORD").unwrap_or("password".to_string()),
-    );
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .build()
-        .expect("Failed to build client");
+    let config_path = env::var("MESH_CONFIG").unwrap_or("mesh.toml".to_string());
+    let default_client = || {
+        reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .expect("Failed to build client")
+    };
+    let (sender_mailbox, sender_client, reciever_mailbox, reciever_client) =
+        match Config::load(&config_path) {
+            Ok(config) => (
+                config
+                    .build_mailbox("sender")
+                    .expect("sender mailbox misconfigured"),
+                config
+                    .build_client("sender")
+                    .expect("sender client cert misconfigured"),
+                config
+                    .build_mailbox("reciever")
+                    .expect("reciever mailbox misconfigured"),
+                config
+                    .build_client("reciever")
+                    .expect("reciever client cert misconfigured"),
+            ),
+            Err(e) => {
+                debug!(
+                    "No usable config at {}, falling back to env vars: {:?}",
+                    config_path, e
+                );
+                (
+                    Mailbox::new(
+                        "https://localhost:8700".to_string(),
+                        env::var("MESH_SENDER_MAILBOX_ID").unwrap_or("X26ABC1".to_string()),
+                        env::var("SENDER_MESH_PASSWORD").unwrap_or("password".to_string()),
+                        SHARED_KEY.to_string(),
+                    ),
+                    default_client(),
+                    Mailbox::new(
+                        "https://localhost:8700".to_string(),
+                        env::var("MESH_RECEIVER_MAILBOX_ID").unwrap_or("X26ABC2".to_string()),
+                        env::var("RECIEVER_MESH_PASSWORD").unwrap_or("password".to_string()),
+                        SHARED_KEY.to_string(),
+                    ),
+                    default_client(),
+                )
+            }
+        };
+
+    if env::var("MESH_TUI").is_ok() {
+        let mbox_path = env::var("MESH_MBOX_PATH").unwrap_or("inbox.mbox".to_string());
+        tui::run(sender_client, sender_mailbox, mbox_path)
+            .await
+            .expect("TUI exited with an error");
+        return;
+    }
+
+    let retry_policy = RetryPolicy::default();
     info!("\n\n Performing healthcheck on mesh url");
-    match health_check(&client, &sender_mailbox).await {
+    match with_retry(&retry_policy, &sender_mailbox, || {
+        health_check(&sender_client, &sender_mailbox)
+    })
+    .await
+    {
         Ok(json) => info!("Sender mailbox healthy: {:?}", json["status"]),
         Err(e) => error!("Error: {:?}", e),
     }
-    match health_check(&client, &reciever_mailbox).await {
+    match with_retry(&retry_policy, &reciever_mailbox, || {
+        health_check(&reciever_client, &reciever_mailbox)
+    })
+    .await
+    {
         Ok(json) => info!("Reciever mailbox healthy: {:?}", json["status"]),
         Err(e) => error!("Error: {:?}", e),
     }
     info!("\n\n Performing handshake on mailboxes");
-    match handshake(&client, &sender_mailbox).await {
+    match with_retry(&retry_policy, &sender_mailbox, || {
+        handshake(&sender_client, &sender_mailbox)
+    })
+    .await
+    {
         Ok(json) => info!("Success {:?}", json),
         Err(e) => error!("Failure: {:?}", e),
     }
-    match handshake(&client, &reciever_mailbox).await {
+    match with_retry(&retry_policy, &reciever_mailbox, || {
+        handshake(&reciever_client, &reciever_mailbox)
+    })
+    .await
+    {
         Ok(json) => info!("Success {:?}", json),
         Err(e) => error!("Failure: {:?}", e),
     }
     info!("\n\n Getting message count on mailboxes");
-    match get_message_count(&client, &sender_mailbox).await {
+    match with_retry(&retry_policy, &sender_mailbox, || {
+        get_message_count(&sender_client, &sender_mailbox)
+    })
+    .await
+    {
         Ok(json) => info!("Success {:?}", json),
         Err(e) => error!("Failure: {:?}", e),
     }
-    match get_message_count(&client, &reciever_mailbox).await {
+    match with_retry(&retry_policy, &reciever_mailbox, || {
+        get_message_count(&reciever_client, &reciever_mailbox)
+    })
+    .await
+    {
         Ok(json) => info!("Success {:?}", json),
         Err(e) => error!("Failure: {:?}", e),
     }
-}
-
-async fn generate_token(mailbox: &Mailbox) -> String {
-    let nonce = Uuid::new_v4().to_string();
-    let nonce_count = 0;
 
-    let timestamp = Utc::now().format("%Y%m%d%H%M").to_string();
-    let hmac_msg = format!(
-        "{}:{}:{}:{}:{}",
-        mailbox.id, nonce, nonce_count, mailbox.password, timestamp
+    info!("\n\n Watching reciever mailbox for new messages");
+    let (mut new_messages, watch_handle) = watch(
+        reciever_client.clone(),
+        reciever_mailbox.clone(),
+        Duration::from_secs(30),
     );
-
-    debug!("{:?}", hmac_msg);
-
-    let mut mac =
-        Hmac::<Sha256>::new_from_slice(SHARED_KEY.as_bytes()).expect("can work with any size");
-    mac.update(hmac_msg.as_bytes());
-
-    let hash_code = hex::encode(mac.finalize().into_bytes());
-
-    format!(
-        "{} {}:{}:{}:{}:{}",
-        AUTH_SCHEMA_NAME, mailbox.id, nonce, nonce_count, timestamp, hash_code
-    )
+    tokio::spawn(async move {
+        while let Some(id) = new_messages.recv().await {
+            info!("Watcher saw new message {}", id);
+        }
+    });
+    tokio::time::sleep(Duration::from_secs(90)).await;
+    watch_handle.cancel().await;
 }
 
 async fn generate_headers(mailbox: &Mailbox) -> Result<HashMap<String, String>, MailboxError> {
-    let token = generate_token(mailbox).await;
+    let token = {
+        let mut generator = mailbox.token_generator.lock().await;
+        generator
+            .generate(&mailbox.id, &mailbox.password, &mailbox.shared_key)
+            .to_header_value()
+    };
     let mut headers = HashMap::new();
     headers.insert(
         "accept".to_string(),
@@ -246,7 +293,19 @@ async fn generate_headers(mailbox: &Mailbox) -> Result<HashMap<String, String>,
     Ok(headers)
 }
 
-async fn health_check(client: &Client, mailbox: &Mailbox) -> Result<Value, Error> {
+async fn build_header_map(mailbox: &Mailbox) -> Result<HeaderMap, MailboxError> {
+    let headers = generate_headers(mailbox).await?;
+    let mut header_map = HeaderMap::new();
+    for (key, value) in headers {
+        header_map.insert(
+            key.parse::<reqwest::header::HeaderName>()?,
+            HeaderValue::from_str(&value)?,
+        );
+    }
+    Ok(header_map)
+}
+
+async fn health_check(client: &Client, mailbox: &Mailbox) -> Result<Value, MailboxError> {
     let response = client.get(format!("{}/health", mailbox.url)).send().await?;
 
     if response.status().is_success() {
@@ -254,20 +313,13 @@ async fn health_check(client: &Client, mailbox: &Mailbox) -> Result<Value, Error
         Ok(json_body)
     } else {
         error!("Failed API call with status: {:?}", response.status());
-        Err(response.error_for_status().unwrap_err())
+        Err(mailbox_error_from_response(&response))
     }
 }
 
 async fn handshake(client: &Client, mailbox: &Mailbox) -> Result<Response, MailboxError> {
     let url = format!("{}/messageexchange/{}", mailbox.url, mailbox.id);
-    let headers = generate_headers(mailbox).await?;
-    let mut header_map = HeaderMap::new();
-    for (key, value) in headers {
-        header_map.insert(
-            key.parse::<reqwest::header::HeaderName>()?,
-            HeaderValue::from_str(&value)?,
-        );
-    }
+    let header_map = build_header_map(mailbox).await?;
 
     let response = client.get(url).headers(header_map).send().await?;
 
@@ -276,32 +328,387 @@ async fn handshake(client: &Client, mailbox: &Mailbox) -> Result<Response, Mailb
     if response.status().is_success() {
         Ok(response)
     } else {
-        Err(MailboxError::ReqwestError(
-            response.error_for_status().unwrap_err(),
-        ))
+        Err(mailbox_error_from_response(&response))
     }
 }
 
 async fn get_message_count(client: &Client, mailbox: &Mailbox) -> Result<Response, MailboxError> {
     let url = format!("{}/messageexchange/{}/inbox", mailbox.url, mailbox.id);
-    let headers = generate_headers(mailbox).await?;
-    let mut header_map = HeaderMap::new();
-    for (key, value) in headers {
-        header_map.insert(
-            key.parse::<reqwest::header::HeaderName>().unwrap(),
-            HeaderValue::from_str(&value)?,
+    let header_map = build_header_map(mailbox).await?;
+
+    let response = client.get(url).headers(header_map).send().await?;
+
+    debug!("Raw response is: {:?}", response);
+
+    if response.status().is_success() {
+        Ok(response)
+    } else {
+        Err(mailbox_error_from_response(&response))
+    }
+}
+
+/// Lists the message IDs currently sitting in the mailbox's inbox.
+async fn list_messages(client: &Client, mailbox: &Mailbox) -> Result<Vec<String>, MailboxError> {
+    let response = get_message_count(client, mailbox).await?;
+    let json_body: Value = response.json().await?;
+    let messages = json_body["messages"]
+        .as_array()
+        .ok_or(MailboxError::MissingField("messages"))?
+        .iter()
+        .filter_map(|id| id.as_str().map(str::to_string))
+        .collect();
+    Ok(messages)
+}
+
+/// A listed inbox message enriched with the sender/workflow metadata the
+/// TUI shows in its message list, fetched via a cheap `HEAD` request.
+pub struct InboxSummary {
+    pub id: String,
+    pub sender: String,
+    pub workflow_id: String,
+}
+
+async fn inbox_summaries(
+    client: &Client,
+    mailbox: &Mailbox,
+) -> Result<Vec<InboxSummary>, MailboxError> {
+    let ids = list_messages(client, mailbox).await?;
+    let mut summaries = Vec::with_capacity(ids.len());
+    for id in ids {
+        let url = format!(
+            "{}/messageexchange/{}/inbox/{}",
+            mailbox.url, mailbox.id, id
         );
+        let header_map = build_header_map(mailbox).await?;
+        let response = client.head(url).headers(header_map).send().await?;
+
+        if !response.status().is_success() {
+            return Err(mailbox_error_from_response(&response));
+        }
+
+        let sender = response
+            .headers()
+            .get("mex-from")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown")
+            .to_string();
+        let workflow_id = response
+            .headers()
+            .get("mex-workflowid")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("-")
+            .to_string();
+
+        summaries.push(InboxSummary {
+            id,
+            sender,
+            workflow_id,
+        });
     }
+    Ok(summaries)
+}
 
-    let response = client.get(url).headers(header_map).send().await?;
+/// Splits `data` into `chunk_size`-sized pieces for [`send_message`]. Empty
+/// `data` yields zero chunks; callers still send a single (empty) chunk by
+/// treating `chunks.len().max(1)` as the total.
+fn chunk_data(data: &[u8], chunk_size: usize) -> Vec<&[u8]> {
+    data.chunks(chunk_size.max(1)).collect()
+}
+
+/// Posts the first chunk of a [`send_message`] upload, establishing the
+/// MESH-assigned message ID. Rebuilds headers on every call so it's safe
+/// to hand to [`with_retry`].
+async fn post_first_chunk(
+    client: &Client,
+    mailbox: &Mailbox,
+    url: &str,
+    recipient: &str,
+    total_chunks: usize,
+    chunk: &[u8],
+) -> Result<Response, MailboxError> {
+    let mut header_map = build_header_map(mailbox).await?;
+    header_map.insert(
+        "mex-to".parse::<reqwest::header::HeaderName>()?,
+        HeaderValue::from_str(recipient)?,
+    );
+    header_map.insert(
+        "mex-chunk-range".parse::<reqwest::header::HeaderName>()?,
+        HeaderValue::from_str(&format!("1:{}", total_chunks))?,
+    );
+    header_map.insert(
+        reqwest::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+
+    let response = client
+        .post(url)
+        .headers(header_map)
+        .body(chunk.to_vec())
+        .send()
+        .await?;
 
     debug!("Raw response is: {:?}", response);
 
     if response.status().is_success() {
         Ok(response)
     } else {
-        Err(MailboxError::ReqwestError(
-            response.error_for_status().unwrap_err(),
-        ))
+        Err(mailbox_error_from_response(&response))
+    }
+}
+
+/// Puts a single non-first chunk of an already-started [`send_message`]
+/// upload. Rebuilds headers on every call so it's safe to hand to
+/// [`with_retry`].
+async fn put_chunk(
+    client: &Client,
+    mailbox: &Mailbox,
+    url: &str,
+    chunk_no: usize,
+    total_chunks: usize,
+    chunk: &[u8],
+) -> Result<(), MailboxError> {
+    let mut header_map = build_header_map(mailbox).await?;
+    header_map.insert(
+        "mex-chunk-range".parse::<reqwest::header::HeaderName>()?,
+        HeaderValue::from_str(&format!("{}:{}", chunk_no, total_chunks))?,
+    );
+    header_map.insert(
+        reqwest::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/octet-stream"),
+    );
+
+    let response = client
+        .put(url)
+        .headers(header_map)
+        .body(chunk.to_vec())
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(mailbox_error_from_response(&response))
+    }
+}
+
+/// Sends `data` to `recipient`, chunking the upload whenever it exceeds
+/// `chunk_size` bytes. Returns the MESH-assigned message ID.
+///
+/// Each chunk is individually wrapped in [`with_retry`] rather than the
+/// whole function: retrying the entire send on a failure partway through
+/// would re-POST the first chunk, mint a *new* message ID from the
+/// server, and orphan the original, now-incomplete message.
+async fn send_message(
+    client: &Client,
+    mailbox: &Mailbox,
+    retry_policy: &RetryPolicy,
+    recipient: &str,
+    data: &[u8],
+    chunk_size: usize,
+) -> Result<String, MailboxError> {
+    let url = format!("{}/messageexchange/{}/outbox", mailbox.url, mailbox.id);
+    let chunks = chunk_data(data, chunk_size);
+    let total_chunks = chunks.len().max(1);
+    let first_chunk = chunks.first().copied().unwrap_or(&[]);
+
+    let response = with_retry(retry_policy, mailbox, || {
+        post_first_chunk(client, mailbox, &url, recipient, total_chunks, first_chunk)
+    })
+    .await?;
+
+    let json_body: Value = response.json().await?;
+    let message_id = json_body["messageID"]
+        .as_str()
+        .ok_or(MailboxError::MissingField("messageID"))?
+        .to_string();
+
+    for (index, chunk) in chunks.iter().enumerate().skip(1) {
+        let chunk_no = index + 1;
+        let chunk_url = format!(
+            "{}/messageexchange/{}/outbox/{}/{}",
+            mailbox.url, mailbox.id, message_id, chunk_no
+        );
+        with_retry(retry_policy, mailbox, || {
+            put_chunk(client, mailbox, &chunk_url, chunk_no, total_chunks, chunk)
+        })
+        .await?;
+    }
+
+    Ok(message_id)
+}
+
+/// Downloads a message, transparently reassembling it if the server
+/// reports multiple chunks via the `Mex-Chunk-Range` response header.
+async fn download_message(
+    client: &Client,
+    mailbox: &Mailbox,
+    msg_id: &str,
+) -> Result<Vec<u8>, MailboxError> {
+    let url = format!(
+        "{}/messageexchange/{}/inbox/{}",
+        mailbox.url, mailbox.id, msg_id
+    );
+    let header_map = build_header_map(mailbox).await?;
+
+    let response = client.get(url).headers(header_map).send().await?;
+
+    debug!("Raw response is: {:?}", response);
+
+    if !response.status().is_success() {
+        return Err(mailbox_error_from_response(&response));
+    }
+
+    let total_chunks = response
+        .headers()
+        .get("mex-chunk-range")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|range| range.split(':').nth(1))
+        .map(str::parse::<u32>)
+        .transpose()
+        .map_err(MailboxError::ParseError)?
+        .unwrap_or(1);
+
+    let mut body = response.bytes().await?.to_vec();
+
+    for chunk_no in 2..=total_chunks {
+        let chunk_url = format!(
+            "{}/messageexchange/{}/inbox/{}/{}",
+            mailbox.url, mailbox.id, msg_id, chunk_no
+        );
+        let header_map = build_header_map(mailbox).await?;
+        let response = client.get(chunk_url).headers(header_map).send().await?;
+
+        if !response.status().is_success() {
+            return Err(mailbox_error_from_response(&response));
+        }
+
+        body.extend(response.bytes().await?);
+    }
+
+    Ok(body)
+}
+
+/// Acknowledges receipt of `msg_id`, removing it from the inbox.
+async fn acknowledge_message(
+    client: &Client,
+    mailbox: &Mailbox,
+    msg_id: &str,
+) -> Result<(), MailboxError> {
+    let url = format!(
+        "{}/messageexchange/{}/inbox/{}/status/acknowledged",
+        mailbox.url, mailbox.id, msg_id
+    );
+    let header_map = build_header_map(mailbox).await?;
+
+    let response = client.put(url).headers(header_map).send().await?;
+
+    debug!("Raw response is: {:?}", response);
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(mailbox_error_from_response(&response))
+    }
+}
+
+/// A handle returned by [`watch`]. Dropping it, or calling `cancel`, stops
+/// the background poll loop at the next tick.
+pub struct WatchHandle {
+    cancel_tx: Option<mpsc::Sender<()>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl WatchHandle {
+    /// Signals the watcher to stop and waits for it to shut down.
+    pub async fn cancel(mut self) {
+        if let Some(tx) = self.cancel_tx.take() {
+            let _ = tx.send(()).await;
+        }
+        let _ = self.task.await;
+    }
+}
+
+/// Polls `mailbox`'s inbox every `interval`, yielding the IDs of messages
+/// that were not present on the previous poll. A fresh token is generated
+/// for every request since MESH tokens are time-bounded.
+///
+/// The returned channel closes once the watcher is cancelled. A failed poll
+/// is logged and the watcher keeps polling on the next tick; it does not
+/// stop when the mailbox stays unreachable.
+fn watch(client: Client, mailbox: Mailbox, interval: Duration) -> (mpsc::Receiver<String>, WatchHandle) {
+    let (event_tx, event_rx) = mpsc::channel(32);
+    let (cancel_tx, mut cancel_rx) = mpsc::channel::<()>(1);
+
+    let task = tokio::spawn(async move {
+        let mut seen: HashSet<String> = HashSet::new();
+        let retry_policy = RetryPolicy::default();
+        loop {
+            tokio::select! {
+                _ = cancel_rx.recv() => {
+                    info!("Watcher for mailbox {} cancelled", mailbox.id);
+                    break;
+                }
+                _ = tokio::time::sleep(interval) => {
+                    match with_retry(&retry_policy, &mailbox, || list_messages(&client, &mailbox)).await {
+                        Ok(messages) => {
+                            for id in &messages {
+                                if seen.insert(id.clone()) && event_tx.send(id.clone()).await.is_err() {
+                                    return;
+                                }
+                            }
+                            seen.retain(|id| messages.contains(id));
+                        }
+                        Err(e) => error!("Watcher poll failed for mailbox {}: {:?}", mailbox.id, e),
+                    }
+                }
+            }
+        }
+    });
+
+    (
+        event_rx,
+        WatchHandle {
+            cancel_tx: Some(cancel_tx),
+            task,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::chunk_data;
+
+    #[test]
+    fn exact_multiple_of_chunk_size_splits_evenly() {
+        let data = vec![0u8; 20];
+        let chunks = chunk_data(&data, 10);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), 10);
+        assert_eq!(chunks[1].len(), 10);
+    }
+
+    #[test]
+    fn one_byte_over_a_chunk_boundary_spills_into_a_second_chunk() {
+        let data = vec![0u8; 11];
+        let chunks = chunk_data(&data, 10);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), 10);
+        assert_eq!(chunks[1].len(), 1);
+    }
+
+    #[test]
+    fn one_byte_under_a_chunk_boundary_stays_in_a_single_chunk() {
+        let data = vec![0u8; 9];
+        let chunks = chunk_data(&data, 10);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].len(), 9);
+    }
+
+    #[test]
+    fn empty_data_yields_no_chunks_but_total_chunks_is_still_one() {
+        let data: Vec<u8> = Vec::new();
+        let chunks = chunk_data(&data, 10);
+        assert!(chunks.is_empty());
+        assert_eq!(chunks.len().max(1), 1);
     }
 }
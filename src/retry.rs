@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use log::warn;
+use rand::Rng;
+
+/// Policy controlling how [`with_retry`] backs off between attempts.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(30),
+            jitter: Duration::from_millis(250),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// `min(max_delay, base_delay * 2^attempt)` plus a random amount of
+    /// jitter up to `self.jitter`, so that multiple clients backing off at
+    /// once don't retry in lockstep.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self.base_delay.saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX));
+        let capped = exp.min(self.max_delay);
+        let jitter_ms = rand::thread_rng().gen_range(0..=self.jitter.as_millis() as u64);
+        capped.saturating_add(Duration::from_millis(jitter_ms))
+    }
+}
+
+/// Whether a [`crate::Mailbox`] is currently reachable. Updated by
+/// [`with_retry`] so callers (such as the TUI's status bar) can surface
+/// connection health.
+#[derive(Debug, Clone)]
+pub enum IsOnline {
+    Online,
+    Offline {
+        since: chrono::DateTime<chrono::Utc>,
+        last_error: String,
+    },
+}
+
+/// Implemented by the error types that flow through MESH calls so
+/// [`with_retry`] can decide whether a failure is worth retrying.
+pub trait Retryable {
+    /// Connection errors, HTTP 5xx, and HTTP 429 are retryable; 4xx auth
+    /// failures and everything else are not.
+    fn is_retryable(&self) -> bool;
+    /// The server's `Retry-After` delay, if it supplied one.
+    fn retry_after(&self) -> Option<Duration>;
+}
+
+/// Calls `f`, retrying on [`Retryable`] errors with exponential backoff
+/// until `policy.max_attempts` is exhausted. Updates `mailbox`'s
+/// [`IsOnline`] status to online on success and to offline on a
+/// *retryable* failure, so the caller can observe connection health
+/// alongside the result; non-retryable errors (bad auth, a malformed
+/// recipient, etc.) are returned without touching connection state.
+pub async fn with_retry<F, Fut, T, E>(
+    policy: &RetryPolicy,
+    mailbox: &crate::Mailbox,
+    mut f: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+    E: Retryable + std::fmt::Debug,
+{
+    let mut attempt = 0;
+    loop {
+        match f().await {
+            Ok(value) => {
+                mailbox.mark_online().await;
+                return Ok(value);
+            }
+            Err(err) => {
+                if !err.is_retryable() {
+                    return Err(err);
+                }
+                mailbox.mark_offline(format!("{:?}", err)).await;
+
+                if attempt + 1 >= policy.max_attempts {
+                    return Err(err);
+                }
+
+                let delay = err.retry_after().unwrap_or_else(|| policy.backoff(attempt));
+                warn!(
+                    "Retryable error on attempt {}/{}: {:?}; retrying in {:?}",
+                    attempt + 1,
+                    policy.max_attempts,
+                    err,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Mailbox, MailboxError};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: Duration::from_millis(1),
+        }
+    }
+
+    fn mailbox() -> Mailbox {
+        Mailbox::new(
+            "https://example.invalid".to_string(),
+            "X26ABC1".to_string(),
+            "password".to_string(),
+            "shared-key".to_string(),
+        )
+    }
+
+    fn not_found() -> MailboxError {
+        MailboxError::HttpStatus {
+            status: reqwest::StatusCode::NOT_FOUND,
+            retry_after: None,
+        }
+    }
+
+    fn server_error() -> MailboxError {
+        MailboxError::HttpStatus {
+            status: reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn backoff_never_exceeds_max_delay_plus_jitter() {
+        let policy = policy();
+        for attempt in 0..10 {
+            assert!(policy.backoff(attempt) <= policy.max_delay + policy.jitter);
+        }
+    }
+
+    #[test]
+    fn backoff_settles_at_max_delay_once_the_exponent_overtakes_it() {
+        let policy = policy();
+        // attempt 10 => base_delay * 2^10, far past max_delay, so the
+        // pre-jitter component should be exactly max_delay.
+        let delay = policy.backoff(10);
+        assert!(delay >= policy.max_delay);
+        assert!(delay <= policy.max_delay + policy.jitter);
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_before_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+            jitter: Duration::from_millis(0),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn http_status_is_retryable_only_for_5xx_and_429() {
+        assert!(!not_found().is_retryable());
+        assert!(server_error().is_retryable());
+        assert!(MailboxError::HttpStatus {
+            status: reqwest::StatusCode::TOO_MANY_REQUESTS,
+            retry_after: None,
+        }
+        .is_retryable());
+    }
+
+    #[tokio::test]
+    async fn with_retry_does_not_mark_offline_on_a_non_retryable_error() {
+        let mailbox = mailbox();
+        let result: Result<(), MailboxError> =
+            with_retry(&policy(), &mailbox, || async { Err(not_found()) }).await;
+
+        assert!(result.is_err());
+        assert!(matches!(mailbox.is_online().await, IsOnline::Online));
+    }
+
+    #[tokio::test]
+    async fn with_retry_marks_offline_on_a_retryable_error_then_online_on_success() {
+        let mailbox = mailbox();
+        let calls = AtomicU32::new(0);
+        let mailbox_ref = &mailbox;
+
+        let result = with_retry(&policy(), &mailbox, || {
+            let attempt = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    Err(server_error())
+                } else {
+                    // The first attempt's retryable failure has marked it offline.
+                    assert!(matches!(
+                        mailbox_ref.is_online().await,
+                        IsOnline::Offline { .. }
+                    ));
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert!(matches!(mailbox.is_online().await, IsOnline::Online));
+    }
+}
@@ -0,0 +1,354 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+/// A downloaded MESH message along with the metadata needed to display or
+/// re-send it without another round trip to the server.
+pub struct StoredMessage {
+    pub id: String,
+    pub sender: String,
+    pub workflow_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub body: Vec<u8>,
+}
+
+/// Durably persists downloaded messages so an inbox survives restarts.
+/// Implemented by [`MboxStore`] and [`MaildirStore`], both of which can
+/// also [`load`](Store::load) back everything they've previously stored,
+/// so the app can repopulate its inbox without re-querying the server.
+pub trait Store {
+    fn store(&mut self, message: &StoredMessage) -> io::Result<()>;
+    fn load(&self) -> io::Result<Vec<StoredMessage>>;
+}
+
+fn header_block(message: &StoredMessage) -> String {
+    format!(
+        "X-Mesh-Message-Id: {}\r\nX-Mesh-Sender: {}\r\nX-Mesh-Workflow-Id: {}\r\nDate: {}\r\n\r\n",
+        message.id,
+        message.sender,
+        message.workflow_id.as_deref().unwrap_or(""),
+        message.timestamp.to_rfc2822(),
+    )
+}
+
+/// The fields carried by [`header_block`], parsed back out of stored bytes.
+struct ParsedHeaders {
+    id: String,
+    sender: String,
+    workflow_id: Option<String>,
+    timestamp: DateTime<Utc>,
+}
+
+fn invalid_data(reason: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.into())
+}
+
+/// Parses a [`header_block`] off the front of `raw`, returning the parsed
+/// headers and the remaining bytes (the message body).
+fn parse_header_block(raw: &[u8]) -> io::Result<(ParsedHeaders, &[u8])> {
+    let text = std::str::from_utf8(raw).map_err(|e| invalid_data(e.to_string()))?;
+    let (header_text, _) = text
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| invalid_data("missing header/body separator"))?;
+    let body = &raw[header_text.len() + "\r\n\r\n".len()..];
+
+    let mut id = None;
+    let mut sender = None;
+    let mut workflow_id = None;
+    let mut timestamp = None;
+    for line in header_text.split("\r\n") {
+        let (key, value) = line
+            .split_once(": ")
+            .ok_or_else(|| invalid_data(format!("malformed header line `{}`", line)))?;
+        match key {
+            "X-Mesh-Message-Id" => id = Some(value.to_string()),
+            "X-Mesh-Sender" => sender = Some(value.to_string()),
+            "X-Mesh-Workflow-Id" => {
+                workflow_id = (!value.is_empty()).then(|| value.to_string());
+            }
+            "Date" => {
+                timestamp = Some(
+                    DateTime::parse_from_rfc2822(value)
+                        .map_err(|e| invalid_data(e.to_string()))?
+                        .with_timezone(&Utc),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let headers = ParsedHeaders {
+        id: id.ok_or_else(|| invalid_data("missing X-Mesh-Message-Id header"))?,
+        sender: sender.ok_or_else(|| invalid_data("missing X-Mesh-Sender header"))?,
+        workflow_id,
+        timestamp: timestamp.ok_or_else(|| invalid_data("missing Date header"))?,
+    };
+    Ok((headers, body))
+}
+
+/// Appends each message to a single mbox file, escaping any in-body lines
+/// that start with `From ` so they aren't mistaken for the next postmark.
+pub struct MboxStore {
+    file: File,
+    path: PathBuf,
+}
+
+impl MboxStore {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(MboxStore { file, path })
+    }
+
+    fn escape_body(body: &[u8]) -> Vec<u8> {
+        let mut escaped = Vec::with_capacity(body.len());
+        for line in body.split_inclusive(|&b| b == b'\n') {
+            if line.starts_with(b"From ") {
+                escaped.extend_from_slice(b">");
+            }
+            escaped.extend_from_slice(line);
+        }
+        escaped
+    }
+
+    fn unescape_body(escaped: &[u8]) -> Vec<u8> {
+        let mut body = Vec::with_capacity(escaped.len());
+        for line in escaped.split_inclusive(|&b| b == b'\n') {
+            if line.starts_with(b">From ") {
+                body.extend_from_slice(&line[1..]);
+            } else {
+                body.extend_from_slice(line);
+            }
+        }
+        body
+    }
+
+    /// Splits raw mbox contents into the byte ranges of each "From " postmark
+    /// block, i.e. a postmark line, a [`header_block`], and an escaped body.
+    fn postmark_blocks(contents: &[u8]) -> Vec<&[u8]> {
+        let mut starts = vec![0];
+        for i in 0..contents.len().saturating_sub(1) {
+            if contents[i] == b'\n' && contents[i + 1..].starts_with(b"From ") {
+                starts.push(i + 1);
+            }
+        }
+        starts.push(contents.len());
+        starts
+            .windows(2)
+            .map(|w| &contents[w[0]..w[1]])
+            .filter(|block| !block.is_empty())
+            .collect()
+    }
+
+    fn parse_block(block: &[u8]) -> io::Result<StoredMessage> {
+        let postmark_end = block
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| invalid_data("mbox block missing postmark line"))?;
+        let (headers, escaped_body) = parse_header_block(&block[postmark_end + 1..])?;
+        // Strip the two blank-line newlines store() appends after the body.
+        let body_end = escaped_body.len().saturating_sub(2);
+        Ok(StoredMessage {
+            id: headers.id,
+            sender: headers.sender,
+            workflow_id: headers.workflow_id,
+            timestamp: headers.timestamp,
+            body: Self::unescape_body(&escaped_body[..body_end]),
+        })
+    }
+}
+
+impl Store for MboxStore {
+    fn store(&mut self, message: &StoredMessage) -> io::Result<()> {
+        writeln!(
+            self.file,
+            "From {} {}",
+            message.sender,
+            message.timestamp.format("%a %b %e %H:%M:%S %Y")
+        )?;
+        self.file.write_all(header_block(message).as_bytes())?;
+        self.file.write_all(&Self::escape_body(&message.body))?;
+        writeln!(self.file)?;
+        writeln!(self.file)?;
+        self.file.flush()
+    }
+
+    fn load(&self) -> io::Result<Vec<StoredMessage>> {
+        let contents = fs::read(&self.path)?;
+        Self::postmark_blocks(&contents)
+            .into_iter()
+            .map(Self::parse_block)
+            .collect()
+    }
+}
+
+/// Writes each message as a file under `new/`, then atomically renames it
+/// into `cur/`, per the Maildir convention. An alternative [`Store`] backend
+/// to [`MboxStore`]; not wired into the TUI by default, but kept available
+/// for deployments that prefer a Maildir-based mailbox.
+#[allow(dead_code)]
+pub struct MaildirStore {
+    new_dir: PathBuf,
+    cur_dir: PathBuf,
+}
+
+#[allow(dead_code)]
+impl MaildirStore {
+    pub fn open(base: impl AsRef<Path>) -> io::Result<Self> {
+        let base = base.as_ref();
+        let new_dir = base.join("new");
+        let cur_dir = base.join("cur");
+        fs::create_dir_all(&new_dir)?;
+        fs::create_dir_all(&cur_dir)?;
+        Ok(MaildirStore { new_dir, cur_dir })
+    }
+}
+
+impl Store for MaildirStore {
+    fn store(&mut self, message: &StoredMessage) -> io::Result<()> {
+        let file_name = format!("{}.{}.mesh", message.timestamp.timestamp(), message.id);
+        let new_path = self.new_dir.join(&file_name);
+
+        let mut file = File::create(&new_path)?;
+        file.write_all(header_block(message).as_bytes())?;
+        file.write_all(&message.body)?;
+        file.flush()?;
+        drop(file);
+
+        fs::rename(&new_path, self.cur_dir.join(&file_name))
+    }
+
+    fn load(&self) -> io::Result<Vec<StoredMessage>> {
+        let mut messages = Vec::new();
+        for entry in fs::read_dir(&self.cur_dir)? {
+            let raw = fs::read(entry?.path())?;
+            let (headers, body) = parse_header_block(&raw)?;
+            messages.push(StoredMessage {
+                id: headers.id,
+                sender: headers.sender,
+                workflow_id: headers.workflow_id,
+                timestamp: headers.timestamp,
+                body: body.to_vec(),
+            });
+        }
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn sample_message() -> StoredMessage {
+        StoredMessage {
+            id: "20260730000000000001".to_string(),
+            sender: "X26ABC1".to_string(),
+            workflow_id: Some("WORKFLOW1".to_string()),
+            timestamp: Utc::now(),
+            body: b"From the body\nsecond line".to_vec(),
+        }
+    }
+
+    // RFC 2822 timestamps only carry second precision, so load-path tests
+    // use a timestamp with no sub-second component to round-trip exactly.
+    fn sample_message_at(id: &str, epoch_secs: i64) -> StoredMessage {
+        StoredMessage {
+            id: id.to_string(),
+            sender: "X26ABC1".to_string(),
+            workflow_id: Some("WORKFLOW1".to_string()),
+            timestamp: Utc.timestamp_opt(epoch_secs, 0).unwrap(),
+            body: b"From the body\nsecond line".to_vec(),
+        }
+    }
+
+    #[test]
+    fn mbox_store_round_trips_a_message_to_disk() {
+        let path = std::env::temp_dir().join(format!("nhs-mesh-cli-test-{}.mbox", Uuid::new_v4()));
+        let message = sample_message();
+
+        let mut store = MboxStore::open(&path).expect("open mbox store");
+        store.store(&message).expect("store message");
+
+        let contents = fs::read_to_string(&path).expect("read mbox file");
+        assert!(contents.contains(&message.id));
+        assert!(contents.contains(">From the body"));
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn mbox_store_load_returns_previously_stored_messages() {
+        let path = std::env::temp_dir().join(format!("nhs-mesh-cli-test-{}.mbox", Uuid::new_v4()));
+        let first = sample_message_at("MSG1", 1_700_000_000);
+        let second = sample_message_at("MSG2", 1_700_000_100);
+
+        let mut store = MboxStore::open(&path).expect("open mbox store");
+        store.store(&first).expect("store first message");
+        store.store(&second).expect("store second message");
+        drop(store);
+
+        let loaded = MboxStore::open(&path)
+            .expect("reopen mbox store")
+            .load()
+            .expect("load stored messages");
+
+        assert_eq!(loaded.len(), 2);
+        for (stored, loaded) in [first, second].iter().zip(loaded.iter()) {
+            assert_eq!(loaded.id, stored.id);
+            assert_eq!(loaded.sender, stored.sender);
+            assert_eq!(loaded.workflow_id, stored.workflow_id);
+            assert_eq!(loaded.timestamp, stored.timestamp);
+            assert_eq!(loaded.body, stored.body);
+        }
+
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn maildir_store_round_trips_a_message_into_cur() {
+        let base = std::env::temp_dir().join(format!("nhs-mesh-cli-test-{}", Uuid::new_v4()));
+        let message = sample_message();
+
+        let mut store = MaildirStore::open(&base).expect("open maildir store");
+        store.store(&message).expect("store message");
+
+        let cur_entries: Vec<_> = fs::read_dir(base.join("cur"))
+            .expect("read cur dir")
+            .collect();
+        assert_eq!(cur_entries.len(), 1);
+        let stored_path = cur_entries[0].as_ref().expect("dir entry").path();
+        let contents = fs::read_to_string(&stored_path).expect("read stored message");
+        assert!(contents.contains(&message.id));
+
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn maildir_store_load_returns_previously_stored_messages() {
+        let base = std::env::temp_dir().join(format!("nhs-mesh-cli-test-{}", Uuid::new_v4()));
+        let first = sample_message_at("MSG1", 1_700_000_000);
+        let second = sample_message_at("MSG2", 1_700_000_100);
+
+        let mut store = MaildirStore::open(&base).expect("open maildir store");
+        store.store(&first).expect("store first message");
+        store.store(&second).expect("store second message");
+
+        let mut loaded = store.load().expect("load stored messages");
+        loaded.sort_by(|a, b| a.id.cmp(&b.id));
+
+        assert_eq!(loaded.len(), 2);
+        for (stored, loaded) in [first, second].iter().zip(loaded.iter()) {
+            assert_eq!(loaded.id, stored.id);
+            assert_eq!(loaded.sender, stored.sender);
+            assert_eq!(loaded.workflow_id, stored.workflow_id);
+            assert_eq!(loaded.timestamp, stored.timestamp);
+            assert_eq!(loaded.body, stored.body);
+        }
+
+        fs::remove_dir_all(&base).ok();
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+use uuid::Uuid;
+
+pub const AUTH_SCHEMA_NAME: &str = "NHSMESH";
+
+/// A single rendered MESH auth token, with its components exposed
+/// individually so the HMAC and counter logic can be exercised without
+/// going through the full header-generation path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthToken {
+    pub mailbox_id: String,
+    pub nonce: String,
+    pub nonce_count: u32,
+    pub timestamp: String,
+    pub hash: String,
+}
+
+impl AuthToken {
+    pub fn nonce_count_hex(&self) -> String {
+        format!("{:08x}", self.nonce_count)
+    }
+
+    /// Renders the `Authorization` header value MESH expects.
+    pub fn to_header_value(&self) -> String {
+        format!(
+            "{} {}:{}:{}:{}:{}",
+            AUTH_SCHEMA_NAME,
+            self.mailbox_id,
+            self.nonce,
+            self.nonce_count_hex(),
+            self.timestamp,
+            self.hash
+        )
+    }
+
+    /// Parses a header value produced by [`AuthToken::to_header_value`].
+    /// Exposed (alongside [`AuthToken::verify`]) so the HMAC and
+    /// counter logic can be exercised without a live MESH server;
+    /// this client doesn't otherwise need to parse its own tokens back.
+    #[allow(dead_code)]
+    pub fn parse(header_value: &str) -> Option<AuthToken> {
+        let (schema, rest) = header_value.split_once(' ')?;
+        if schema != AUTH_SCHEMA_NAME {
+            return None;
+        }
+        let mut parts = rest.splitn(5, ':');
+        Some(AuthToken {
+            mailbox_id: parts.next()?.to_string(),
+            nonce: parts.next()?.to_string(),
+            nonce_count: u32::from_str_radix(parts.next()?, 16).ok()?,
+            timestamp: parts.next()?.to_string(),
+            hash: parts.next()?.to_string(),
+        })
+    }
+
+    /// Recomputes the HMAC-SHA256 over `id:nonce:nonce_count:password:timestamp`
+    /// and constant-time-compares it against the token's hash.
+    #[allow(dead_code)]
+    pub fn verify(header_value: &str, password: &str, shared_key: &str) -> bool {
+        match AuthToken::parse(header_value) {
+            Some(token) => {
+                let expected = compute_hash(
+                    &token.mailbox_id,
+                    &token.nonce,
+                    &token.nonce_count_hex(),
+                    password,
+                    &token.timestamp,
+                    shared_key,
+                );
+                constant_time_eq(expected.as_bytes(), token.hash.as_bytes())
+            }
+            None => false,
+        }
+    }
+}
+
+fn compute_hash(
+    mailbox_id: &str,
+    nonce: &str,
+    nonce_count_hex: &str,
+    password: &str,
+    timestamp: &str,
+    shared_key: &str,
+) -> String {
+    let hmac_msg = format!(
+        "{}:{}:{}:{}:{}",
+        mailbox_id, nonce, nonce_count_hex, password, timestamp
+    );
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(shared_key.as_bytes()).expect("can work with any size");
+    mac.update(hmac_msg.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[allow(dead_code)]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Generates [`AuthToken`]s for a single mailbox, reusing the current
+/// nonce (and incrementing its counter) within its validity window rather
+/// than minting a fresh nonce on every call, matching RFC 2617's
+/// nonce-count reuse semantics.
+#[derive(Debug)]
+pub struct AuthTokenGenerator {
+    nonce: String,
+    nonce_count: u32,
+    issued_at: DateTime<Utc>,
+    /// Set once the current nonce has actually been used by a `generate()`
+    /// call, so the first call can't be mistaken for a reuse just because
+    /// `elapsed` since construction happens to be small.
+    last_used_at: Option<DateTime<Utc>>,
+    validity: Duration,
+}
+
+impl Default for AuthTokenGenerator {
+    /// MESH timestamps carry minute precision, so a one-minute validity
+    /// window keeps the nonce fresh relative to the timestamp it's paired with.
+    fn default() -> Self {
+        AuthTokenGenerator::new(Duration::from_secs(60))
+    }
+}
+
+impl AuthTokenGenerator {
+    pub fn new(validity: Duration) -> Self {
+        AuthTokenGenerator {
+            nonce: Uuid::new_v4().to_string(),
+            nonce_count: 0,
+            issued_at: Utc::now(),
+            last_used_at: None,
+            validity,
+        }
+    }
+
+    pub fn generate(&mut self, mailbox_id: &str, password: &str, shared_key: &str) -> AuthToken {
+        let now = Utc::now();
+        let elapsed = now.signed_duration_since(self.issued_at);
+        let expired = elapsed < chrono::Duration::zero()
+            || elapsed.to_std().unwrap_or(Duration::MAX) > self.validity;
+        if expired {
+            self.nonce = Uuid::new_v4().to_string();
+            self.nonce_count = 0;
+            self.issued_at = now;
+            self.last_used_at = None;
+        } else if self.last_used_at.is_some() {
+            self.nonce_count += 1;
+        }
+        self.last_used_at = Some(now);
+
+        let timestamp = now.format("%Y%m%d%H%M").to_string();
+        let nonce_count_hex = format!("{:08x}", self.nonce_count);
+        let hash = compute_hash(
+            mailbox_id,
+            &self.nonce,
+            &nonce_count_hex,
+            password,
+            &timestamp,
+            shared_key,
+        );
+
+        AuthToken {
+            mailbox_id: mailbox_id.to_string(),
+            nonce: self.nonce.clone(),
+            nonce_count: self.nonce_count,
+            timestamp,
+            hash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_generate_call_keeps_nonce_count_at_zero() {
+        let mut generator = AuthTokenGenerator::default();
+        let first = generator.generate("mailbox", "password", "shared-key");
+        assert_eq!(first.nonce_count, 0);
+    }
+
+    #[test]
+    fn second_generate_call_within_validity_increments_nonce_count() {
+        let mut generator = AuthTokenGenerator::default();
+        let first = generator.generate("mailbox", "password", "shared-key");
+        let second = generator.generate("mailbox", "password", "shared-key");
+        assert_eq!(first.nonce_count, 0);
+        assert_eq!(second.nonce_count, 1);
+        assert_eq!(first.nonce, second.nonce);
+    }
+
+    #[test]
+    fn header_value_round_trips_through_parse_and_verifies() {
+        let mut generator = AuthTokenGenerator::default();
+        let token = generator.generate("mailbox", "password", "shared-key");
+
+        let header_value = token.to_header_value();
+        let parsed = AuthToken::parse(&header_value).expect("header value should parse");
+        assert_eq!(parsed, token);
+
+        assert!(AuthToken::verify(&header_value, "password", "shared-key"));
+        assert!(!AuthToken::verify(&header_value, "wrong-password", "shared-key"));
+    }
+}
@@ -0,0 +1,422 @@
+use std::io::{self, stdout};
+use std::time::Duration;
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    ExecutableCommand,
+};
+use ratatui::{backend::CrosstermBackend, prelude::*, widgets::*};
+use reqwest::Client;
+
+use crate::retry::{with_retry, IsOnline, RetryPolicy};
+use crate::store::{MboxStore, Store, StoredMessage};
+use crate::{
+    acknowledge_message, download_message, handshake, inbox_summaries, send_message,
+    InboxSummary, Mailbox, DEFAULT_CHUNK_SIZE,
+};
+
+/// Which field of the compose screen keystrokes are currently edit.
+enum ComposeField {
+    Recipient,
+    Body,
+}
+
+/// The screens of the interactive client. Which MESH operations are
+/// reachable is gated entirely by which state we're in: download and
+/// acknowledge, for example, only make sense once we're `Browsing`.
+enum AppState {
+    Disconnected,
+    Authenticated,
+    Browsing {
+        messages: Vec<InboxSummary>,
+        selected: usize,
+        opened_body: Option<Vec<u8>>,
+    },
+    Composing {
+        recipient: String,
+        body: String,
+        field: ComposeField,
+    },
+}
+
+struct App {
+    client: Client,
+    mailbox: Mailbox,
+    store: MboxStore,
+    retry_policy: RetryPolicy,
+    state: AppState,
+    online: IsOnline,
+    status: String,
+    should_quit: bool,
+}
+
+impl App {
+    fn new(client: Client, mailbox: Mailbox, store: MboxStore) -> Self {
+        App {
+            client,
+            mailbox,
+            store,
+            retry_policy: RetryPolicy::default(),
+            state: AppState::Disconnected,
+            online: IsOnline::Online,
+            status: "press 'h' to handshake".to_string(),
+            should_quit: false,
+        }
+    }
+
+    fn connection_label(&self) -> String {
+        match &self.online {
+            IsOnline::Online => "online".to_string(),
+            IsOnline::Offline { since, last_error } => {
+                format!("offline since {} ({})", since.to_rfc3339(), last_error)
+            }
+        }
+    }
+
+    async fn dispatch(&mut self, key: KeyCode) {
+        let composing = matches!(self.state, AppState::Composing { .. });
+        if key == KeyCode::Char('q') && !composing {
+            self.should_quit = true;
+            return;
+        }
+
+        self.state = match std::mem::replace(&mut self.state, AppState::Disconnected) {
+            AppState::Disconnected => {
+                if key == KeyCode::Char('h') {
+                    match with_retry(&self.retry_policy, &self.mailbox, || {
+                        handshake(&self.client, &self.mailbox)
+                    })
+                    .await
+                    {
+                        Ok(_) => {
+                            self.status = "authenticated".to_string();
+                            AppState::Authenticated
+                        }
+                        Err(e) => {
+                            self.status = format!("handshake failed: {:?}", e);
+                            AppState::Disconnected
+                        }
+                    }
+                } else {
+                    AppState::Disconnected
+                }
+            }
+            AppState::Authenticated => {
+                if key == KeyCode::Char('r') {
+                    match with_retry(&self.retry_policy, &self.mailbox, || {
+                        inbox_summaries(&self.client, &self.mailbox)
+                    })
+                    .await
+                    {
+                        Ok(messages) => {
+                            self.status = format!("{} messages", messages.len());
+                            AppState::Browsing {
+                                messages,
+                                selected: 0,
+                                opened_body: None,
+                            }
+                        }
+                        Err(e) => {
+                            self.status = format!("refresh failed: {:?}", e);
+                            AppState::Authenticated
+                        }
+                    }
+                } else if key == KeyCode::Char('c') {
+                    self.status = "composing: type recipient, Tab for body, Enter to send".to_string();
+                    AppState::Composing {
+                        recipient: String::new(),
+                        body: String::new(),
+                        field: ComposeField::Recipient,
+                    }
+                } else {
+                    AppState::Authenticated
+                }
+            }
+            AppState::Browsing {
+                mut messages,
+                mut selected,
+                mut opened_body,
+            } => {
+                match key {
+                    KeyCode::Down if !messages.is_empty() => {
+                        selected = (selected + 1).min(messages.len() - 1);
+                    }
+                    KeyCode::Up => selected = selected.saturating_sub(1),
+                    KeyCode::Enter => {
+                        if let Some(entry) = messages.get(selected) {
+                            match with_retry(&self.retry_policy, &self.mailbox, || {
+                                download_message(&self.client, &self.mailbox, &entry.id)
+                            })
+                            .await
+                            {
+                                Ok(body) => {
+                                    let stored = StoredMessage {
+                                        id: entry.id.clone(),
+                                        sender: entry.sender.clone(),
+                                        workflow_id: Some(entry.workflow_id.clone()),
+                                        timestamp: chrono::Utc::now(),
+                                        body: body.clone(),
+                                    };
+                                    match self.store.store(&stored) {
+                                        Ok(()) => {
+                                            self.status = format!("downloaded and stored {}", entry.id)
+                                        }
+                                        Err(e) => {
+                                            self.status =
+                                                format!("downloaded {} but store failed: {}", entry.id, e)
+                                        }
+                                    }
+                                    opened_body = Some(body);
+                                }
+                                Err(e) => self.status = format!("download failed: {:?}", e),
+                            }
+                        }
+                    }
+                    KeyCode::Char('a') => {
+                        if let Some(entry) = messages.get(selected) {
+                            let id = entry.id.clone();
+                            match with_retry(&self.retry_policy, &self.mailbox, || {
+                                acknowledge_message(&self.client, &self.mailbox, &id)
+                            })
+                            .await
+                            {
+                                Ok(()) => {
+                                    self.status = format!("acknowledged {}", id);
+                                    messages.retain(|m| m.id != id);
+                                    selected = selected.min(messages.len().saturating_sub(1));
+                                    opened_body = None;
+                                }
+                                Err(e) => self.status = format!("acknowledge failed: {:?}", e),
+                            }
+                        }
+                    }
+                    KeyCode::Char('r') => match with_retry(&self.retry_policy, &self.mailbox, || {
+                        inbox_summaries(&self.client, &self.mailbox)
+                    })
+                    .await
+                    {
+                        Ok(refreshed) => {
+                            self.status = format!("{} messages", refreshed.len());
+                            messages = refreshed;
+                            selected = 0;
+                            opened_body = None;
+                        }
+                        Err(e) => self.status = format!("refresh failed: {:?}", e),
+                    },
+                    KeyCode::Char('c') => {
+                        self.status =
+                            "composing: type recipient, Tab for body, Enter to send".to_string();
+                        self.state = AppState::Composing {
+                            recipient: String::new(),
+                            body: String::new(),
+                            field: ComposeField::Recipient,
+                        };
+                        return;
+                    }
+                    _ => {}
+                }
+                AppState::Browsing {
+                    messages,
+                    selected,
+                    opened_body,
+                }
+            }
+            AppState::Composing {
+                mut recipient,
+                mut body,
+                mut field,
+            } => {
+                match key {
+                    KeyCode::Esc => {
+                        self.status = "compose cancelled".to_string();
+                        return self.finish_dispatch(AppState::Authenticated);
+                    }
+                    KeyCode::Tab => {
+                        field = match field {
+                            ComposeField::Recipient => ComposeField::Body,
+                            ComposeField::Body => ComposeField::Recipient,
+                        };
+                    }
+                    KeyCode::Backspace => match field {
+                        ComposeField::Recipient => {
+                            recipient.pop();
+                        }
+                        ComposeField::Body => {
+                            body.pop();
+                        }
+                    },
+                    KeyCode::Enter => match field {
+                        ComposeField::Recipient => field = ComposeField::Body,
+                        ComposeField::Body => {
+                            // send_message retries each chunk internally, so
+                            // it isn't wrapped in with_retry here too - doing
+                            // so would re-POST the first chunk on a partial
+                            // failure and mint a second, duplicate message.
+                            match send_message(
+                                &self.client,
+                                &self.mailbox,
+                                &self.retry_policy,
+                                &recipient,
+                                body.as_bytes(),
+                                DEFAULT_CHUNK_SIZE,
+                            )
+                            .await
+                            {
+                                Ok(message_id) => {
+                                    self.status = format!("sent as {}", message_id);
+                                    return self.finish_dispatch(AppState::Authenticated);
+                                }
+                                Err(e) => self.status = format!("send failed: {:?}", e),
+                            }
+                        }
+                    },
+                    KeyCode::Char(c) => match field {
+                        ComposeField::Recipient => recipient.push(c),
+                        ComposeField::Body => body.push(c),
+                    },
+                    _ => {}
+                }
+                AppState::Composing {
+                    recipient,
+                    body,
+                    field,
+                }
+            }
+        };
+    }
+
+    fn finish_dispatch(&mut self, state: AppState) {
+        self.state = state;
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let layout = Layout::new(
+        Direction::Vertical,
+        [Constraint::Min(0), Constraint::Length(1)],
+    )
+    .split(frame.size());
+
+    match &app.state {
+        AppState::Disconnected => {
+            frame.render_widget(
+                Paragraph::new("Press 'h' to handshake, 'q' to quit")
+                    .block(Block::default().borders(Borders::ALL).title("NHS MESH")),
+                layout[0],
+            );
+        }
+        AppState::Authenticated => {
+            frame.render_widget(
+                Paragraph::new("Press 'r' to refresh inbox, 'c' to compose, 'q' to quit")
+                    .block(Block::default().borders(Borders::ALL).title("NHS MESH")),
+                layout[0],
+            );
+        }
+        AppState::Browsing {
+            messages,
+            selected,
+            opened_body,
+        } => {
+            let panes = Layout::new(
+                Direction::Horizontal,
+                [Constraint::Percentage(50), Constraint::Percentage(50)],
+            )
+            .split(layout[0]);
+
+            let rows: Vec<ListItem> = messages
+                .iter()
+                .enumerate()
+                .map(|(i, m)| {
+                    let line = format!("{:<14} {:<20} {}", m.id, m.sender, m.workflow_id);
+                    if i == *selected {
+                        ListItem::new(line).style(Style::default().add_modifier(Modifier::REVERSED))
+                    } else {
+                        ListItem::new(line)
+                    }
+                })
+                .collect();
+            frame.render_widget(
+                List::new(rows).block(Block::default().borders(Borders::ALL).title("Inbox")),
+                panes[0],
+            );
+
+            let body_text = opened_body
+                .as_ref()
+                .map(|b| String::from_utf8_lossy(b).to_string())
+                .unwrap_or_else(|| "Select a message and press Enter to download".to_string());
+            frame.render_widget(
+                Paragraph::new(body_text).block(Block::default().borders(Borders::ALL).title("Message")),
+                panes[1],
+            );
+        }
+        AppState::Composing {
+            recipient,
+            body,
+            field,
+        } => {
+            let panes = Layout::new(
+                Direction::Vertical,
+                [Constraint::Length(3), Constraint::Min(0)],
+            )
+            .split(layout[0]);
+
+            let recipient_title = match field {
+                ComposeField::Recipient => "To (editing)",
+                ComposeField::Body => "To",
+            };
+            frame.render_widget(
+                Paragraph::new(recipient.as_str())
+                    .block(Block::default().borders(Borders::ALL).title(recipient_title)),
+                panes[0],
+            );
+
+            let body_title = match field {
+                ComposeField::Recipient => "Body",
+                ComposeField::Body => "Body (editing, Enter to send)",
+            };
+            frame.render_widget(
+                Paragraph::new(body.as_str())
+                    .block(Block::default().borders(Borders::ALL).title(body_title)),
+                panes[1],
+            );
+        }
+    }
+
+    let status = Paragraph::new(format!("[{}] {}", app.connection_label(), app.status))
+        .block(Block::default().borders(Borders::TOP));
+    frame.render_widget(status, layout[1]);
+}
+
+/// Runs the interactive client until the user quits, persisting every
+/// downloaded message to `mbox_path` via [`MboxStore`].
+pub async fn run(client: Client, mailbox: Mailbox, mbox_path: impl AsRef<std::path::Path>) -> io::Result<()> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let store = MboxStore::open(mbox_path)?;
+    let previously_stored = store.load().map(|m| m.len()).unwrap_or(0);
+    let mut app = App::new(client, mailbox, store);
+    app.status = format!(
+        "{} message(s) already on disk from previous sessions; press 'h' to handshake",
+        previously_stored
+    );
+
+    while !app.should_quit {
+        app.online = app.mailbox.is_online().await;
+        terminal.draw(|frame| draw(frame, &app))?;
+
+        if event::poll(Duration::from_millis(50))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    app.dispatch(key.code).await;
+                }
+            }
+        }
+    }
+
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+    Ok(())
+}